@@ -16,24 +16,48 @@ use clap::{Command, Arg, ArgMatches};
 
 use regex::{Regex, Captures};
 
+use glob::glob;
+use url::Url;
+use serde::{Serialize, Deserialize};
+use once_cell::sync::Lazy;
+
 
 use std::io;
+use std::io::Read;
 use std::fs;
 use std::fmt;
 use std::process;
+use std::time::Duration;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 
-#[derive(Clone, Copy, Debug)]
+// How long to wait on a single remote fetch before giving up on it
+const DEFAULT_REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug)]
 enum FileSearchProcessorError {
     FileTypeConversionFailed,
+    AliasRuleConversionFailed,
+    AliasCollision(String),
+    FileNameCollision(String),
+    AliasDerivationFailed(PathBuf),
+    RemoteFileNameUnknown(Url),
+    EnvVarExpansionFailed(String),
+    NonUtf8SearchPattern(PathBuf),
 }
 
 impl fmt::Display for FileSearchProcessorError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
+        match self {
             FileSearchProcessorError::FileTypeConversionFailed => write!(f, "Invalid file type"),
+            FileSearchProcessorError::AliasRuleConversionFailed => write!(f, "Invalid alias derivation rule"),
+            FileSearchProcessorError::AliasCollision(alias) => write!(f, "Alias '{}' is already mapped to a different file", alias),
+            FileSearchProcessorError::FileNameCollision(file_name) => write!(f, "Two different aliases resolve to the same output file name '{}'", file_name),
+            FileSearchProcessorError::AliasDerivationFailed(path) => write!(f, "Failed to derive an alias for '{}'", path.display()),
+            FileSearchProcessorError::RemoteFileNameUnknown(url) => write!(f, "Could not derive a file name from '{}'", url),
+            FileSearchProcessorError::EnvVarExpansionFailed(name) => write!(f, "Environment variable '{}' used in a configured path is not set", name),
+            FileSearchProcessorError::NonUtf8SearchPattern(pattern) => write!(f, "Search pattern '{}' is not valid UTF-8", pattern.display()),
         }
     }
 }
@@ -42,7 +66,7 @@ impl std::error::Error for FileSearchProcessorError {}
 
 #[derive(Clone, Copy)]
 enum FileType {
-    Link, Image,
+    Link, Image, Include,
 }
 
 impl TryFrom<&str> for FileType {
@@ -52,94 +76,532 @@ impl TryFrom<&str> for FileType {
         match value {
             "image" => Ok(FileType::Image),
             "link" => Ok(FileType::Link),
+            "include" => Ok(FileType::Include),
             _ => Err(FileSearchProcessorError::FileTypeConversionFailed)
         }
     }
 }
 
+// How an alias is derived for a file discovered through a `search` glob pattern, as opposed to
+// a file explicitly named in the `files` config.
+enum AliasRule {
+    // Use the file stem, e.g. `logo.png` -> `logo`
+    Stem,
+    // Use the full file name, e.g. `logo.png` -> `logo.png`
+    Filename,
+    // Capture group 1 of a regex matched against the path relative to the book root
+    Regex(Regex),
+}
+
+impl TryFrom<&str> for AliasRule {
+    type Error = FileSearchProcessorError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "stem" => Ok(AliasRule::Stem),
+            "filename" => Ok(AliasRule::Filename),
+            _ => Err(FileSearchProcessorError::AliasRuleConversionFailed),
+        }
+    }
+}
+
+impl AliasRule {
+    fn derive(&self, root: &Path, path: &Path) -> Result<String> {
+        match self {
+            AliasRule::Stem => path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|stem| stem.to_owned())
+                .ok_or_else(|| FileSearchProcessorError::AliasDerivationFailed(path.to_owned()).into()),
+            AliasRule::Filename => path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.to_owned())
+                .ok_or_else(|| FileSearchProcessorError::AliasDerivationFailed(path.to_owned()).into()),
+            AliasRule::Regex(re) => {
+                let relative = path.strip_prefix(root).unwrap_or(path);
+                let relative = relative.to_string_lossy();
+
+                re.captures(&relative)
+                    .and_then(|captures| captures.get(1))
+                    .map(|capture| capture.as_str().to_owned())
+                    .ok_or_else(|| FileSearchProcessorError::AliasDerivationFailed(path.to_owned()).into())
+            },
+        }
+    }
+}
+
+// A glob pattern to search for files outside the book directory, paired with the file type and
+// alias derivation rule to apply to each match.
+struct SearchEntry {
+    pattern: String,
+    file_type: FileType,
+    alias_rule: AliasRule,
+}
+
+// Where a configured or discovered file actually lives. A resolver for either case converges
+// here so `get_link_path`/`get_insert_text` only need to care about the bytes ending up on disk.
+#[derive(Clone, Debug, PartialEq)]
+enum Source {
+    Local(PathBuf),
+    Remote(Url),
+}
+
+impl Source {
+    // A `path` beginning with `http://` or `https://` is a remote source; anything else is a
+    // path on disk (relative to, or outside of, the book directory).
+    fn parse(path: &str) -> Source {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            if let Ok(url) = Url::parse(path) {
+                return Source::Remote(url);
+            }
+        }
+
+        Source::Local(PathBuf::from(path))
+    }
+
+    fn file_name(&self) -> Option<String> {
+        match self {
+            Source::Local(path) => path.file_name().and_then(|name| name.to_str()).map(|name| name.to_owned()),
+            Source::Remote(url) => url.path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_owned()),
+        }
+    }
+}
+
 struct FileCache {
     output_dir: PathBuf,
-    alias_to_path: HashMap<String, PathBuf>,
+    // Where remote `include` sources are cached. Unlike `output_dir`, this lives outside `src/`
+    // so mdbook never publishes it; `include` only ever needs the bytes for line-extraction, not
+    // a world-readable copy.
+    include_cache_dir: PathBuf,
+    alias_to_source: HashMap<String, Source>,
     alias_to_type: HashMap<String, FileType>,
+    // Tracks which alias currently owns each derived output file name, so two aliases whose
+    // sources merely share a basename can't silently clobber each other in `copy_files`.
+    file_name_to_alias: HashMap<String, String>,
+    remote_timeout: Duration,
 }
 
 impl FileCache {
     pub fn new(root: PathBuf) -> Result<FileCache> {
         let output_dir = root.join("src").join("_files");
+        let include_cache_dir = root.join(".file-search-cache");
 
         Ok(
             FileCache {
                 output_dir,
-                alias_to_path: Default::default(),
+                include_cache_dir,
+                alias_to_source: Default::default(),
                 alias_to_type: Default::default(),
+                file_name_to_alias: Default::default(),
+                remote_timeout: DEFAULT_REMOTE_TIMEOUT,
             }
         )
     }
 
-    pub fn copy_files(&self) -> Result<()> {
+    pub fn set_remote_timeout(&mut self, timeout: Duration) {
+        self.remote_timeout = timeout;
+    }
+
+    pub fn copy_files(&mut self) -> Result<()> {
         if !self.output_dir.exists() {
             fs::create_dir(&self.output_dir)?;
         }
 
-        for (_, path) in self.alias_to_path.iter() {
-            if let Some(file_name) = path.file_name() {
-                let output_file = self.output_dir.clone().join(file_name);
+        let mut manifest = load_manifest(&self.output_dir);
+        let mut remote_manifest = load_remote_manifest(&self.output_dir);
+        let mut unavailable = Vec::new();
 
-                // Copy the source file to the destination if it doesn't exist or if the existing file is out of date
-                let should_copy = if output_file.exists() {
-                    // Get modified time of the source and output file
-                    let source_modified = fs::metadata(path)?.modified()?;
-                    let output_modified = fs::metadata(output_file.clone())?.modified()?;
+        for (alias, source) in self.alias_to_source.iter() {
+            let file_name = match source.file_name() {
+                Some(file_name) => file_name,
+                None => {
+                    if let Source::Remote(url) = source {
+                        eprintln!("Warning: {}", FileSearchProcessorError::RemoteFileNameUnknown(url.clone()));
+                    }
+                    unavailable.push(alias.clone());
+                    continue;
+                },
+            };
+
+            // `include` aliases are read straight from their `Source` when rendering (see
+            // `get_include_text`), so they're never copied into the publicly-served output
+            // directory. A remote `include` source still needs *somewhere* on disk to live, so
+            // it goes to a private cache instead.
+            if let Some(FileType::Include) = self.alias_to_type.get(alias) {
+                if let Source::Remote(url) = source {
+                    if !self.include_cache_dir.exists() {
+                        fs::create_dir(&self.include_cache_dir)?;
+                    }
 
-                    output_modified < source_modified
-                }
-                else {
-                    true
-                };
+                    let cached_file = self.include_cache_dir.join(&file_name);
 
-                if should_copy {
-                    fs::copy(path, output_file)?;
+                    match fetch_remote(url, &cached_file, self.remote_timeout, remote_manifest.get(alias)) {
+                        Ok(Some(entry)) => { remote_manifest.insert(alias.clone(), entry); },
+                        Ok(None) => {},
+                        Err(err) => {
+                            eprintln!("Warning: failed to download '{}' for alias '{}': {}", url, alias, err);
+                            unavailable.push(alias.clone());
+                        },
+                    }
                 }
+
+                continue;
             }
+
+            let output_file = self.output_dir.clone().join(file_name);
+
+            match source {
+                Source::Local(path) => {
+                    // Copy the source file to the destination if it doesn't exist or if its content hash
+                    // has changed since the last recorded copy
+                    let hash = hash_file(path)?;
+                    let should_copy = !output_file.exists() || manifest.get(alias) != Some(&hash);
+
+                    if should_copy {
+                        fs::copy(path, output_file)?;
+                    }
+
+                    manifest.insert(alias.clone(), hash);
+                },
+                // Re-check the URL on every build via a conditional request (`If-None-Match` /
+                // `If-Modified-Since`, built from the validators in `remote_manifest`); a server
+                // that doesn't return either validator is refetched unconditionally each time.
+                Source::Remote(url) => {
+                    match fetch_remote(url, &output_file, self.remote_timeout, remote_manifest.get(alias)) {
+                        Ok(Some(entry)) => { remote_manifest.insert(alias.clone(), entry); },
+                        Ok(None) => {},
+                        Err(err) => {
+                            eprintln!("Warning: failed to download '{}' for alias '{}': {}", url, alias, err);
+                            unavailable.push(alias.clone());
+                        },
+                    }
+                },
+            }
+        }
+
+        // A remote fetch failure shouldn't abort the whole book build; instead drop the alias so
+        // `{{#find}}` falls back to its "unknown" placeholder.
+        for alias in &unavailable {
+            self.alias_to_source.remove(alias);
+            self.alias_to_type.remove(alias);
         }
 
+        manifest.retain(|alias, _| self.alias_to_source.contains_key(alias));
+        save_manifest(&self.output_dir, &manifest)?;
+
+        remote_manifest.retain(|alias, _| self.alias_to_source.contains_key(alias));
+        save_remote_manifest(&self.output_dir, &remote_manifest)?;
+
         Ok(())
     }
 
-    pub fn get_insert_text(&self, alias: &str) -> Option<String> {
-        let link = self.get_link_path(alias);
-        let file_type = self.alias_to_type.get(alias);
+    pub fn get_insert_text(&self, alias: &str, line_range: Option<(usize, usize)>, attrs: &HashMap<String, String>) -> Option<String> {
+        let file_type = self.alias_to_type.get(alias)?;
 
-        if let (Some(link), Some(file_type)) = (link, file_type) {
-            match *file_type {
-                FileType::Link => Some(format!("[{}]({})", alias, link)),
-                FileType::Image => Some(format!("![Image not found]({})", link)),
-            }
-        }
-        else {
-            None
+        match *file_type {
+            FileType::Link => {
+                let link = self.get_link_path(alias)?;
+                let label = attrs.get("text").map(|text| text.as_str()).unwrap_or(alias);
+                Some(format!("[{}]({})", label, link))
+            },
+            FileType::Image => {
+                let link = self.get_link_path(alias)?;
+                let alt = attrs.get("alt").map(|alt| alt.as_str()).unwrap_or("Image not found");
+
+                if attrs.contains_key("width") || attrs.contains_key("height") {
+                    let mut img = format!(r#"<img src="{}" alt="{}""#, link, alt);
+
+                    if let Some(width) = attrs.get("width") {
+                        img.push_str(&format!(r#" width="{}""#, width));
+                    }
+                    if let Some(height) = attrs.get("height") {
+                        img.push_str(&format!(r#" height="{}""#, height));
+                    }
+
+                    img.push_str("/>");
+                    Some(img)
+                }
+                else {
+                    Some(format!("![{}]({})", alt, link))
+                }
+            },
+            FileType::Include => self.get_include_text(alias, line_range),
         }
     }
 
     pub fn get_link_path(&self, alias: &str) -> Option<String> {
-        self.alias_to_path.get(alias).map(|path| {
-            path
-                .file_name()
-                .map(|filename| filename.to_str())
-                .flatten()
-                .map(|filename| format!("./_files/{}", filename))
-        }).flatten()
+        self.alias_to_source.get(alias)
+            .and_then(|source| source.file_name())
+            .map(|filename| format!("./_files/{}", filename))
     }
 
-    pub fn add_file(&mut self, alias: &str, path: &str, file_type: &str) -> Result<()> {
-        self.alias_to_path.insert(alias.to_owned(), PathBuf::from(path));
+    // Inline the file's contents (optionally restricted to `line_range`, 1-indexed and
+    // inclusive) as a fenced code block whose language is inferred from the file extension.
+    fn get_include_text(&self, alias: &str, line_range: Option<(usize, usize)>) -> Option<String> {
+        let source = self.alias_to_source.get(alias)?;
+        let file_name = source.file_name()?;
+
+        // `include` sources are never copied into the public output directory (see
+        // `copy_files`), so read them from wherever they actually live: the original path for a
+        // local source, or the private include cache for a downloaded remote one.
+        let contents = match source {
+            Source::Local(path) => fs::read_to_string(path).ok()?,
+            Source::Remote(_) => fs::read_to_string(self.include_cache_dir.join(&file_name)).ok()?,
+        };
+
+        let contents = match line_range {
+            // Clamp against the real line count rather than trusting `start`/`end` as given —
+            // both come straight from the directive's `\d+` capture groups, so a huge or
+            // out-of-order value (e.g. `:0:18446744073709551615`) must not be allowed to
+            // overflow the arithmetic below.
+            Some((start, end)) => {
+                let lines: Vec<&str> = contents.lines().collect();
+                let total = lines.len();
+
+                let start_idx = start.saturating_sub(1).min(total);
+                let end_idx = end.max(start).min(total);
+
+                lines[start_idx..end_idx].join("\n")
+            },
+            None => contents,
+        };
+
+        let language = Path::new(&file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(language_for_extension)
+            .unwrap_or_default();
+
+        Some(format!("```{}\n{}\n```", language, contents))
+    }
 
+    pub fn add_file(&mut self, alias: &str, path: &str, file_type: &str) -> Result<()> {
         let file_type: FileType = file_type.try_into()?;
+        let path = expand_path(path)?;
+        self.register(alias, Source::parse(&path), file_type)
+    }
+
+    // Register a discovered or explicitly configured file under `alias`, failing if the alias
+    // is already mapped to a different file, or if a different alias already derives the same
+    // output file name (which would otherwise clobber it in `copy_files`).
+    pub fn register(&mut self, alias: &str, source: Source, file_type: FileType) -> Result<()> {
+        if let Some(existing) = self.alias_to_source.get(alias) {
+            if existing != &source {
+                return Err(FileSearchProcessorError::AliasCollision(alias.to_owned()).into());
+            }
+        }
+        else if let Some(file_name) = source.file_name() {
+            if let Some(existing_alias) = self.file_name_to_alias.get(&file_name) {
+                if existing_alias != alias {
+                    return Err(FileSearchProcessorError::FileNameCollision(file_name).into());
+                }
+            }
+
+            self.file_name_to_alias.insert(file_name, alias.to_owned());
+        }
+
+        self.alias_to_source.insert(alias.to_owned(), source);
         self.alias_to_type.insert(alias.to_owned(), file_type);
         Ok(())
     }
 }
 
+// Expand a leading `~` and any `$VAR` / `${VAR}` references in a configured path. Book authors
+// share `book.toml` across machines where assets live under different roots, so an unset
+// variable is treated as a hard error rather than silently copying nothing.
+fn expand_path(path: &str) -> Result<String> {
+    expand_env_vars(&expand_home(path)?)
+}
+
+fn expand_home(path: &str) -> Result<String> {
+    if path == "~" || path.starts_with("~/") {
+        let home = std::env::var("HOME").map_err(|_| FileSearchProcessorError::EnvVarExpansionFailed("HOME".to_owned()))?;
+
+        if path == "~" {
+            Ok(home)
+        }
+        else {
+            Ok(format!("{}{}", home, &path[1..]))
+        }
+    }
+    else {
+        Ok(path.to_owned())
+    }
+}
+
+fn expand_env_vars(path: &str) -> Result<String> {
+    let re = Regex::new(r"\$\{(?P<braced>[A-Za-z_][A-Za-z0-9_]*)\}|\$(?P<bare>[A-Za-z_][A-Za-z0-9_]*)")?;
+
+    let mut expanded = String::new();
+    let mut last_end = 0;
+
+    for captures in re.captures_iter(path) {
+        let whole_match = captures.get(0).unwrap();
+        expanded.push_str(&path[last_end..whole_match.start()]);
+
+        let var_name = captures.name("braced").or_else(|| captures.name("bare")).unwrap().as_str();
+        let value = std::env::var(var_name).map_err(|_| FileSearchProcessorError::EnvVarExpansionFailed(var_name.to_owned()))?;
+        expanded.push_str(&value);
+
+        last_end = whole_match.end();
+    }
+    expanded.push_str(&path[last_end..]);
+
+    Ok(expanded)
+}
+
+// Attributes a {{#find}} directive may carry, e.g. `alt="Company logo" width=300`
+const KNOWN_FIND_ATTRIBUTES: &[&str] = &["alt", "width", "height", "text"];
+
+// Compiled once and reused across every `{{#find}}` match in the book.
+static FIND_ATTRIBUTE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"([A-Za-z_][A-Za-z0-9_]*)=(?:"([^"]*)"|(\S+))"#).unwrap());
+
+// Parse the trailing `key=value` / `key="value with spaces"` attributes off a {{#find}}
+// directive. Unrecognized keys are warned about and dropped rather than failing the build.
+fn parse_find_attributes(attrs: &str) -> HashMap<String, String> {
+    let mut parsed = HashMap::new();
+
+    for captures in FIND_ATTRIBUTE_RE.captures_iter(attrs) {
+        let key = &captures[1];
+        let value = captures.get(2).or_else(|| captures.get(3)).map(|value| value.as_str()).unwrap_or_default();
+
+        if KNOWN_FIND_ATTRIBUTES.contains(&key) {
+            parsed.insert(key.to_owned(), value.to_owned());
+        }
+        else {
+            eprintln!("Warning: ignoring unknown {{{{#find}}}} attribute '{}'", key);
+        }
+    }
+
+    parsed
+}
+
+// Map a file extension to the language tag markdown renderers expect after the fence, e.g.
+// ```rust. Unrecognized extensions fall back to an untagged fence.
+fn language_for_extension(extension: &str) -> &'static str {
+    match extension {
+        "rs" => "rust",
+        "toml" => "toml",
+        "json" => "json",
+        "yml" | "yaml" => "yaml",
+        "py" => "python",
+        "js" => "javascript",
+        "ts" => "typescript",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "sh" => "bash",
+        "md" => "markdown",
+        "html" => "html",
+        "css" => "css",
+        "xml" => "xml",
+        _ => "",
+    }
+}
+
+// Name of the manifest file, kept alongside the copied files in `output_dir`, that records the
+// content hash each alias was copied at so staleness doesn't depend on source/output mtimes
+// (which a fresh `git clone` or CI checkout resets).
+const MANIFEST_FILE_NAME: &str = ".manifest.json";
+
+// Hash a file's contents with a fast, non-cryptographic hash, streaming it in chunks so large
+// files don't need to be read into memory all at once.
+fn hash_file(path: &Path) -> Result<u32> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+fn load_manifest(output_dir: &Path) -> HashMap<String, u32> {
+    fs::File::open(output_dir.join(MANIFEST_FILE_NAME))
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(output_dir: &Path, manifest: &HashMap<String, u32>) -> Result<()> {
+    let file = fs::File::create(output_dir.join(MANIFEST_FILE_NAME))?;
+    serde_json::to_writer(file, manifest)?;
+    Ok(())
+}
+
+// Name of the manifest that records the caching validators (`ETag`/`Last-Modified`) a remote
+// source was last fetched with, keyed by alias. Lives alongside `MANIFEST_FILE_NAME` in
+// `output_dir` even for remote `include` sources, which are themselves cached elsewhere.
+const REMOTE_MANIFEST_FILE_NAME: &str = ".remote_manifest.json";
+
+// Caching validators captured from a remote source's response headers, used to make the next
+// build's request conditional instead of blindly trusting the cached file's presence.
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct RemoteCacheEntry {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+fn load_remote_manifest(output_dir: &Path) -> HashMap<String, RemoteCacheEntry> {
+    fs::File::open(output_dir.join(REMOTE_MANIFEST_FILE_NAME))
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+fn save_remote_manifest(output_dir: &Path, manifest: &HashMap<String, RemoteCacheEntry>) -> Result<()> {
+    let file = fs::File::create(output_dir.join(REMOTE_MANIFEST_FILE_NAME))?;
+    serde_json::to_writer(file, manifest)?;
+    Ok(())
+}
+
+// Fetch `url` into `output_file`, bounded by `timeout`. When `cached` carries a validator from a
+// previous fetch and `output_file` already exists, the request is made conditional (`If-None-Match`
+// / `If-Modified-Since`); a `304 Not Modified` response means the cached file is still current, in
+// which case `output_file` is left untouched and `Ok(None)` is returned. Otherwise the body is
+// written to `output_file` and the new validators (if any) are returned.
+fn fetch_remote(url: &Url, output_file: &Path, timeout: Duration, cached: Option<&RemoteCacheEntry>) -> std::result::Result<Option<RemoteCacheEntry>, Box<dyn std::error::Error>> {
+    let mut request = ureq::get(url.as_str()).timeout(timeout);
+
+    if output_file.exists() {
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.set("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.set("If-Modified-Since", last_modified);
+            }
+        }
+    }
+
+    let response = request.call()?;
+
+    if response.status() == 304 {
+        return Ok(None);
+    }
+
+    let entry = RemoteCacheEntry {
+        etag: response.header("ETag").map(|value| value.to_owned()),
+        last_modified: response.header("Last-Modified").map(|value| value.to_owned()),
+    };
+
+    let mut reader = response.into_reader();
+    let mut file = fs::File::create(output_file)?;
+    io::copy(&mut reader, &mut file)?;
+
+    Ok(Some(entry))
+}
+
 #[derive(Default)]
 struct FileSearch;
 
@@ -153,6 +615,10 @@ impl Preprocessor for FileSearch {
 
         // Load the file alias mapping from the supplied preprocessor configuration
         if let Some(cfg) = ctx.config.get_preprocessor(self.name()) {
+            if let Some(timeout_secs) = cfg.get("remote_timeout_secs").and_then(|value| value.as_integer()) {
+                cache.set_remote_timeout(Duration::from_secs(timeout_secs.max(0) as u64));
+            }
+
             if let Some(toml::Value::Array(files)) = cfg.get("files") {
                 for file in files.iter().filter_map(|item| item.as_table()) {
                     let alias = file.get("alias").map(|value| value.as_str()).flatten();
@@ -166,6 +632,19 @@ impl Preprocessor for FileSearch {
             }
         }
 
+        // Load glob-based search entries from the supplied preprocessor configuration and
+        // register every match in the cache under its derived alias
+        if let Some(cfg) = ctx.config.get_preprocessor(self.name()) {
+            if let Some(toml::Value::Array(search)) = cfg.get("search") {
+                let entries: Vec<SearchEntry> = search.iter()
+                    .filter_map(|item| item.as_table())
+                    .filter_map(parse_search_entry)
+                    .collect();
+
+                discover_search_files(&PathBuf::from(ctx.root.clone()), &entries, &mut cache)?;
+            }
+        }
+
         // Copy configured files into the output directory
         cache.copy_files()?;
 
@@ -173,16 +652,27 @@ impl Preprocessor for FileSearch {
         // the book directory
 
         // The alias syntax looks like: {{#find foo}}
-        // Where `foo` is the alias defined in the `files` preprocessor
-        let re = Regex::new(r"\{\{\#find\s([\d\w]+)\}\}")?;
+        // Where `foo` is an alias defined in the `files` config or derived from a `search` entry. For an `include` alias, a
+        // trailing `:start:end` (1-indexed, inclusive) restricts the inlined content to that line range,
+        // e.g. {{#find app_cfg:10:25}}. Trailing `key=value` attributes customize the rendered
+        // output, e.g. {{#find logo alt="Company logo" width=300}}
+        let re = Regex::new(r#"\{\{\#find\s([\d\w.]+)(?::(\d+):(\d+))?((?:\s+[A-Za-z_][A-Za-z0-9_]*=(?:"[^"]*"|\S+))*)\s*\}\}"#)?;
 
         book.for_each_mut(move |item: &mut BookItem|{
             if let BookItem::Chapter(ref mut chapter) = item {
                 chapter.content = re.replace_all(chapter.content.as_str(), |groups: &Captures| {
                     let alias = &groups[1];
-                    // let link_path = cache.get_link_path(alias).unwrap_or("unknown".to_string());
-                    // format!("[{}]({})", alias, link_path)
-                    cache.get_insert_text(alias).unwrap_or("unknown".to_string())
+                    let line_range = match (groups.get(2), groups.get(3)) {
+                        (Some(start), Some(end)) => {
+                            let start: usize = start.as_str().parse().unwrap_or(1);
+                            let end: usize = end.as_str().parse().unwrap_or(start);
+                            Some((start, end))
+                        },
+                        _ => None,
+                    };
+                    let attrs = parse_find_attributes(groups.get(4).map(|attrs| attrs.as_str()).unwrap_or(""));
+
+                    cache.get_insert_text(alias, line_range, &attrs).unwrap_or("unknown".to_string())
                 }).to_string();
             }
         });
@@ -195,6 +685,54 @@ impl Preprocessor for FileSearch {
     }
 }
 
+// Parse a single `[[preprocessor.file-search.search]]` table into a `SearchEntry`. Entries
+// missing a required key, or with an unrecognized `type`/`alias` rule, are skipped.
+fn parse_search_entry(entry: &toml::value::Table) -> Option<SearchEntry> {
+    let pattern = entry.get("pattern").map(|value| value.as_str()).flatten()?;
+    let file_type: FileType = entry.get("type").map(|value| value.as_str()).flatten()?.try_into().ok()?;
+
+    let alias_rule = match entry.get("alias") {
+        Some(toml::Value::String(rule)) => rule.as_str().try_into().ok()?,
+        Some(toml::Value::Table(table)) => {
+            let pattern = table.get("regex").map(|value| value.as_str()).flatten()?;
+            AliasRule::Regex(Regex::new(pattern).ok()?)
+        },
+        _ => return None,
+    };
+
+    Some(SearchEntry {
+        pattern: pattern.to_owned(),
+        file_type,
+        alias_rule,
+    })
+}
+
+// Walk every `SearchEntry`'s glob pattern (relative to the book root) and register each match
+// in `cache` under its derived alias.
+fn discover_search_files(root: &Path, entries: &[SearchEntry], cache: &mut FileCache) -> Result<()> {
+    for entry in entries {
+        let pattern = expand_path(&entry.pattern)?;
+        let pattern = root.join(pattern);
+        let pattern = pattern.to_str().ok_or_else(|| FileSearchProcessorError::NonUtf8SearchPattern(pattern.clone()))?;
+
+        for found in glob(pattern)? {
+            let path = found?;
+
+            // A pattern like `assets/**` also matches directories; skip them rather than
+            // registering a "file" that will fail to hash/copy later.
+            if !path.is_file() {
+                continue;
+            }
+
+            let alias = entry.alias_rule.derive(root, &path)?;
+
+            cache.register(&alias, Source::Local(path), entry.file_type)?;
+        }
+    }
+
+    Ok(())
+}
+
 fn main() {
     let matches = make_app().get_matches();
 
@@ -250,3 +788,86 @@ fn make_app() -> Command<'static> {
                 .about("Check whether a renderer is supported by this preprocessor"),
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_allows_the_same_alias_to_be_registered_twice_with_an_identical_source() {
+        let mut cache = FileCache::new(PathBuf::from("/book")).unwrap();
+
+        cache.register("logo", Source::Local(PathBuf::from("/assets/logo.png")), FileType::Image).unwrap();
+        cache.register("logo", Source::Local(PathBuf::from("/assets/logo.png")), FileType::Image).unwrap();
+    }
+
+    #[test]
+    fn register_rejects_an_alias_remapped_to_a_different_source() {
+        let mut cache = FileCache::new(PathBuf::from("/book")).unwrap();
+
+        cache.register("logo", Source::Local(PathBuf::from("/assets/logo.png")), FileType::Image).unwrap();
+        let result = cache.register("logo", Source::Local(PathBuf::from("/assets/other.png")), FileType::Image);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn register_rejects_two_aliases_that_derive_the_same_output_file_name() {
+        let mut cache = FileCache::new(PathBuf::from("/book")).unwrap();
+
+        cache.register("windows_settings", Source::Local(PathBuf::from("/assets/windows/settings.png")), FileType::Image).unwrap();
+        let result = cache.register("macos_settings", Source::Local(PathBuf::from("/assets/macos/settings.png")), FileType::Image);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn alias_rule_stem_uses_the_file_stem() {
+        let root = PathBuf::from("/book");
+        let path = PathBuf::from("/book/assets/logo.png");
+
+        assert_eq!(AliasRule::Stem.derive(&root, &path).unwrap(), "logo");
+    }
+
+    #[test]
+    fn alias_rule_filename_uses_the_full_file_name() {
+        let root = PathBuf::from("/book");
+        let path = PathBuf::from("/book/assets/logo.png");
+
+        assert_eq!(AliasRule::Filename.derive(&root, &path).unwrap(), "logo.png");
+    }
+
+    #[test]
+    fn alias_rule_regex_captures_group_one_against_the_path_relative_to_root() {
+        let root = PathBuf::from("/book");
+        let path = PathBuf::from("/book/assets/icons/settings.png");
+        let rule = AliasRule::Regex(Regex::new(r"icons/(\w+)\.png$").unwrap());
+
+        assert_eq!(rule.derive(&root, &path).unwrap(), "settings");
+    }
+
+    #[test]
+    fn alias_rule_regex_fails_when_the_pattern_does_not_match() {
+        let root = PathBuf::from("/book");
+        let path = PathBuf::from("/book/assets/logo.png");
+        let rule = AliasRule::Regex(Regex::new(r"icons/(\w+)\.png$").unwrap());
+
+        assert!(rule.derive(&root, &path).is_err());
+    }
+
+    #[test]
+    fn parse_find_attributes_reads_quoted_and_bare_values() {
+        let attrs = parse_find_attributes(r#"alt="Company logo" width=300"#);
+
+        assert_eq!(attrs.get("alt").map(String::as_str), Some("Company logo"));
+        assert_eq!(attrs.get("width").map(String::as_str), Some("300"));
+    }
+
+    #[test]
+    fn parse_find_attributes_drops_unknown_keys() {
+        let attrs = parse_find_attributes("bogus=1 alt=\"ok\"");
+
+        assert!(!attrs.contains_key("bogus"));
+        assert_eq!(attrs.get("alt").map(String::as_str), Some("ok"));
+    }
+}